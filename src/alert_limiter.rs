@@ -0,0 +1,274 @@
+use crate::alert_transport::AlertTransport;
+use crate::socket::{ConnectionState, WSMessage};
+use governor::clock::DefaultClock;
+use governor::state::keyed::DefaultKeyedStateStore;
+use governor::{Quota, RateLimiter};
+use log::error;
+use std::collections::HashMap;
+use std::num::NonZeroU32;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{watch, Mutex};
+
+type KeyedLimiter = RateLimiter<String, DefaultKeyedStateStore<String>, DefaultClock>;
+
+/// How often the idle sweep checks for suppressed summaries to flush.
+const IDLE_FLUSH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How long a subject must go without a new suppression before its pending
+/// summary is flushed on its own, in case the subject never sends again
+/// (e.g. it leaves `Critical` for good right after being suppressed).
+const IDLE_FLUSH_THRESHOLD: Duration = Duration::from_secs(10);
+
+/// Tracks alerts suppressed for a subject while its token bucket is empty,
+/// so the eventual coalesced summary can report how many cycles were
+/// dropped and the worst `overall_score` seen during the window.
+#[derive(Debug, Clone, Copy)]
+struct SuppressedWindow {
+    dropped: u32,
+    peak_overall_score: f64,
+    last_suppressed_at: Instant,
+}
+
+impl SuppressedWindow {
+    fn new() -> Self {
+        Self {
+            dropped: 0,
+            peak_overall_score: f64::MIN,
+            last_suppressed_at: Instant::now(),
+        }
+    }
+}
+
+/// Wraps an `AlertTransport` with a per-subject token bucket so a metric
+/// stuck in `Critical` across consecutive one-second cycles can't flood
+/// downstream consumers. While a subject's bucket is empty, alerts are
+/// coalesced into a single summary that is flushed once the bucket refills,
+/// or after `IDLE_FLUSH_THRESHOLD` of inactivity via `spawn_idle_flush`.
+pub struct RateLimitedTransport<T: AlertTransport> {
+    inner: T,
+    limiter: KeyedLimiter,
+    suppressed: Mutex<HashMap<String, SuppressedWindow>>,
+}
+
+impl<T: AlertTransport> RateLimitedTransport<T> {
+    /// `max_per_minute` sets each subject's bucket: a burst of up to
+    /// `max_per_minute` alerts, refilled at one alert every
+    /// `60 / max_per_minute` seconds.
+    pub fn new(inner: T, max_per_minute: u32) -> Self {
+        let quota = Quota::per_minute(NonZeroU32::new(max_per_minute).expect("max_per_minute must be > 0"));
+        Self {
+            inner,
+            limiter: RateLimiter::keyed(quota),
+            suppressed: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Sends `msg` if the subject's bucket has room, flushing any coalesced
+    /// summary first; otherwise records the suppression and returns `None`.
+    pub async fn send_or_coalesce(&self, msg: WSMessage, overall_score: f64) -> Result<Option<WSMessage>, String> {
+        let subject = msg.sub.clone();
+
+        if self.limiter.check_key(&subject).is_err() {
+            let mut suppressed = self.suppressed.lock().await;
+            let window = suppressed.entry(subject).or_insert_with(SuppressedWindow::new);
+            window.dropped += 1;
+            window.peak_overall_score = window.peak_overall_score.max(overall_score);
+            window.last_suppressed_at = Instant::now();
+            return Ok(None);
+        }
+
+        if let Some(summary) = self.take_summary(&subject).await {
+            self.inner.send(summary).await?;
+        }
+        Ok(Some(self.inner.send(msg).await?))
+    }
+
+    /// Forwards the wrapped transport's connection health.
+    pub fn connection_state(&self) -> ConnectionState {
+        self.inner.connection_state()
+    }
+
+    async fn take_summary(&self, subject: &str) -> Option<WSMessage> {
+        let mut suppressed = self.suppressed.lock().await;
+        let window = suppressed.remove(subject)?;
+        Self::summary_for(subject, window)
+    }
+
+    fn summary_for(subject: &str, window: SuppressedWindow) -> Option<WSMessage> {
+        if window.dropped == 0 {
+            return None;
+        }
+        Some(WSMessage {
+            sub: subject.to_string(),
+            payload: Some(format!(
+                "Suppressed {} critical cycles for {} (peak overall_score {:.2})",
+                window.dropped, subject, window.peak_overall_score
+            )),
+            reply_sub: None,
+            error: None,
+        })
+    }
+}
+
+impl<T: AlertTransport + 'static> RateLimitedTransport<T> {
+    /// Spawns a background sweep that flushes any subject's pending summary
+    /// once it has sat idle past `IDLE_FLUSH_THRESHOLD`, so a subject that
+    /// never becomes `Critical` again doesn't lose its dropped-cycle count
+    /// waiting on a `send_or_coalesce` call that's never coming. Stops once
+    /// `shutdown` fires.
+    pub fn spawn_idle_flush(self: Arc<Self>, mut shutdown: watch::Receiver<bool>) {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(IDLE_FLUSH_INTERVAL);
+            loop {
+                tokio::select! {
+                    _ = ticker.tick() => {}
+                    _ = shutdown.changed() => return,
+                }
+
+                let due = {
+                    let mut suppressed = self.suppressed.lock().await;
+                    take_idle_windows(&mut suppressed, IDLE_FLUSH_THRESHOLD)
+                };
+
+                for (subject, window) in due {
+                    if let Some(summary) = Self::summary_for(&subject, window) {
+                        if let Err(e) = self.inner.send(summary).await {
+                            error!("Failed to flush idle suppressed-alert summary for {}: {}", subject, e);
+                        }
+                    }
+                }
+            }
+        });
+    }
+}
+
+/// Removes and returns every window idle past `threshold`, leaving fresher
+/// windows in place. Split out from `spawn_idle_flush` so the selection
+/// logic can be unit-tested without waiting on real time.
+fn take_idle_windows(suppressed: &mut HashMap<String, SuppressedWindow>, threshold: Duration) -> Vec<(String, SuppressedWindow)> {
+    let due_subjects: Vec<String> = suppressed
+        .iter()
+        .filter(|(_, window)| window.last_suppressed_at.elapsed() >= threshold)
+        .map(|(subject, _)| subject.clone())
+        .collect();
+
+    due_subjects
+        .into_iter()
+        .filter_map(|subject| suppressed.remove(&subject).map(|window| (subject, window)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use std::sync::Mutex as StdMutex;
+
+    struct RecordingTransport {
+        sent: StdMutex<Vec<WSMessage>>,
+    }
+
+    impl RecordingTransport {
+        fn new() -> Self {
+            Self { sent: StdMutex::new(Vec::new()) }
+        }
+
+        fn sent_count(&self) -> usize {
+            self.sent.lock().unwrap().len()
+        }
+    }
+
+    #[async_trait]
+    impl AlertTransport for RecordingTransport {
+        async fn send(&self, msg: WSMessage) -> Result<WSMessage, String> {
+            self.sent.lock().unwrap().push(WSMessage {
+                sub: msg.sub.clone(),
+                payload: msg.payload.clone(),
+                reply_sub: msg.reply_sub.clone(),
+                error: msg.error.clone(),
+            });
+            Ok(msg)
+        }
+    }
+
+    fn critical_alert(subject: &str) -> WSMessage {
+        WSMessage {
+            sub: subject.to_string(),
+            payload: Some("critical".to_string()),
+            reply_sub: None,
+            error: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn first_alert_for_a_subject_is_sent_immediately() {
+        let transport = RateLimitedTransport::new(RecordingTransport::new(), 1);
+
+        let result = transport.send_or_coalesce(critical_alert("cpu"), 0.9).await.unwrap();
+
+        assert!(result.is_some());
+        assert_eq!(transport.inner.sent_count(), 1);
+    }
+
+    #[tokio::test]
+    async fn subject_over_budget_is_suppressed_and_not_forwarded() {
+        let transport = RateLimitedTransport::new(RecordingTransport::new(), 1);
+        transport.send_or_coalesce(critical_alert("cpu"), 0.9).await.unwrap();
+
+        let result = transport.send_or_coalesce(critical_alert("cpu"), 0.95).await.unwrap();
+
+        assert!(result.is_none());
+        assert_eq!(transport.inner.sent_count(), 1, "suppressed send must not reach the inner transport");
+    }
+
+    #[tokio::test]
+    async fn suppressed_cycles_are_coalesced_with_the_peak_score() {
+        let transport = RateLimitedTransport::new(RecordingTransport::new(), 1);
+        transport.send_or_coalesce(critical_alert("cpu"), 0.9).await.unwrap();
+        transport.send_or_coalesce(critical_alert("cpu"), 0.95).await.unwrap();
+        transport.send_or_coalesce(critical_alert("cpu"), 0.80).await.unwrap();
+
+        let suppressed = transport.suppressed.lock().await;
+        let window = suppressed.get("cpu").expect("suppression window recorded");
+        assert_eq!(window.dropped, 2);
+        assert!((window.peak_overall_score - 0.95).abs() < f64::EPSILON);
+    }
+
+    #[tokio::test]
+    async fn unrelated_subjects_do_not_share_a_budget() {
+        let transport = RateLimitedTransport::new(RecordingTransport::new(), 1);
+        transport.send_or_coalesce(critical_alert("cpu"), 0.9).await.unwrap();
+
+        let result = transport.send_or_coalesce(critical_alert("ram"), 0.9).await.unwrap();
+
+        assert!(result.is_some(), "a fresh subject must get its own budget");
+    }
+
+    #[test]
+    fn take_idle_windows_flushes_only_windows_past_the_threshold() {
+        let mut suppressed = HashMap::new();
+        suppressed.insert(
+            "stale".to_string(),
+            SuppressedWindow {
+                dropped: 3,
+                peak_overall_score: 0.9,
+                last_suppressed_at: Instant::now() - Duration::from_secs(30),
+            },
+        );
+        suppressed.insert("fresh".to_string(), SuppressedWindow::new());
+
+        let due = take_idle_windows(&mut suppressed, Duration::from_secs(10));
+
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].0, "stale");
+        assert!(!suppressed.contains_key("stale"), "flushed windows must be removed");
+        assert!(suppressed.contains_key("fresh"), "fresh windows must be left alone");
+    }
+
+    #[test]
+    fn summary_for_an_empty_window_is_skipped() {
+        let window = SuppressedWindow::new();
+        assert!(RateLimitedTransport::<RecordingTransport>::summary_for("cpu", window).is_none());
+    }
+}
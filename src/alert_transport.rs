@@ -0,0 +1,127 @@
+use crate::client::send_alert;
+use crate::socket::{ConnectionState, WSMessage, WebSocketClient};
+use async_trait::async_trait;
+use std::sync::Arc;
+
+/// Common interface for dispatching an alert and optionally waiting for a
+/// reply, regardless of which transport actually carries it. `WSMessage`'s
+/// `sub`/`reply_sub` fields already mirror a subject + reply-inbox model, so
+/// every backend speaks in terms of it.
+#[async_trait]
+pub trait AlertTransport: Send + Sync {
+    async fn send(&self, msg: WSMessage) -> Result<WSMessage, String>;
+
+    /// Transport health, so callers can annotate outgoing alerts. Backends
+    /// that don't override this (gRPC, NATS) always report `Connected`.
+    fn connection_state(&self) -> ConnectionState {
+        ConnectionState::Connected
+    }
+}
+
+// Lets a boxed-up transport (picked at runtime, see `main`'s `ALERT_TRANSPORT`
+// selection) be used anywhere a concrete `AlertTransport` is expected.
+#[async_trait]
+impl AlertTransport for Arc<dyn AlertTransport> {
+    async fn send(&self, msg: WSMessage) -> Result<WSMessage, String> {
+        (**self).send(msg).await
+    }
+
+    fn connection_state(&self) -> ConnectionState {
+        (**self).connection_state()
+    }
+}
+
+#[async_trait]
+impl AlertTransport for WebSocketClient {
+    async fn send(&self, msg: WSMessage) -> Result<WSMessage, String> {
+        self.send_and_wait_for_reply(msg).await
+    }
+
+    fn connection_state(&self) -> ConnectionState {
+        WebSocketClient::connection_state(self)
+    }
+}
+
+/// Adapts the tonic `send_alert` RPC to the `AlertTransport` interface.
+pub struct GrpcAlertTransport;
+
+#[async_trait]
+impl AlertTransport for GrpcAlertTransport {
+    async fn send(&self, msg: WSMessage) -> Result<WSMessage, String> {
+        let payload = msg.payload.clone().unwrap_or_default();
+        match send_alert(msg.sub.clone(), payload, msg.reply_sub.is_some()).await {
+            Ok(ack) => Ok(WSMessage {
+                sub: ack.subject,
+                payload: Some(ack.payload),
+                reply_sub: None,
+                error: None,
+            }),
+            Err(e) => Ok(WSMessage {
+                sub: msg.sub,
+                payload: None,
+                reply_sub: None,
+                error: Some(e.to_string()),
+            }),
+        }
+    }
+}
+
+/// Subject-based fan-out transport backed by a NATS server. Publishes use
+/// `WSMessage.sub` as the subject; when `reply_sub` is set, the client's
+/// built-in request/reply (with an auto-generated inbox) is used instead of
+/// a hand-rolled timeout + pending-replies map. This fans each alert out to
+/// every subscriber on the subject, which a point-to-point WebSocket can't
+/// do; messages are plain core NATS publishes and are not persisted for
+/// subscribers that are not connected at the time.
+pub struct NatsAlertTransport {
+    client: async_nats::Client,
+}
+
+impl NatsAlertTransport {
+    pub async fn connect(url: &str) -> Result<Self, String> {
+        let client = async_nats::connect(url)
+            .await
+            .map_err(|e| format!("Failed to connect to NATS at {}: {}", url, e))?;
+        Ok(Self { client })
+    }
+}
+
+#[async_trait]
+impl AlertTransport for NatsAlertTransport {
+    async fn send(&self, msg: WSMessage) -> Result<WSMessage, String> {
+        let payload = msg.payload.clone().unwrap_or_default();
+
+        if msg.reply_sub.is_some() {
+            return match self.client.request(msg.sub.clone(), payload.into()).await {
+                Ok(reply) => Ok(WSMessage {
+                    sub: reply.subject.to_string(),
+                    payload: Some(String::from_utf8_lossy(&reply.payload).to_string()),
+                    reply_sub: None,
+                    error: None,
+                }),
+                Err(e) => Ok(WSMessage {
+                    sub: msg.sub,
+                    payload: None,
+                    reply_sub: None,
+                    error: Some(format!("NATS request failed: {}", e)),
+                }),
+            };
+        }
+
+        if let Err(e) = self.client.publish(msg.sub.clone(), payload.into()).await {
+            return Ok(WSMessage {
+                sub: msg.sub,
+                payload: None,
+                reply_sub: None,
+                error: Some(format!("NATS publish failed: {}", e)),
+            });
+        }
+
+        Ok(WSMessage {
+            sub: "NoReplyExpected".to_string(),
+            payload: None,
+            reply_sub: None,
+            error: None,
+        })
+    }
+}
@@ -1,74 +1,215 @@
+mod alert_limiter;
+mod alert_transport;
+mod client;
 mod metrics;
+mod metrics_exporter;
+mod metrics_server;
+mod socket;
+mod types;
 
+use crate::alert_limiter::RateLimitedTransport;
+use crate::alert_transport::{AlertTransport, GrpcAlertTransport, NatsAlertTransport};
 use crate::metrics::{Metric, MetricHistory, MetricStatus, Metrics};
+use crate::metrics_exporter::{MetricsExporter, METRICS_EXPORTER_ADDR};
+use crate::metrics_server::{shutdown_signal, MetricsServer, METRICS_SERVER_ADDR};
+use crate::socket::{WSMessage, WebSocketClient};
 use chrono::Utc;
 use env_logger;
 use extended_isolation_forest::{Forest, ForestOptions};
 use log::{error, info};
 use reqwest;
+use std::env;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
-use std::{env, thread};
 use sysinfo::{Disks, System};
-use tokio::runtime::Runtime;
 
 const MONITORING_INTERVAL: Duration = Duration::from_secs(1);
+const MAX_CRITICAL_ALERTS_PER_MINUTE: u32 = 5;
+const DEFAULT_NATS_URL: &str = "nats://127.0.0.1:4222";
+const DEFAULT_ALERT_WS_URL: &str = "ws://127.0.0.1:8765";
+
+/// Picks the alert backend from `ALERT_TRANSPORT` (`grpc` by default, or
+/// `nats`/`websocket`), falling back to gRPC if the requested backend can't
+/// be reached at startup.
+async fn select_alert_transport() -> Arc<dyn AlertTransport> {
+    match env::var("ALERT_TRANSPORT").as_deref() {
+        Ok("nats") => {
+            let url = env::var("NATS_URL").unwrap_or_else(|_| DEFAULT_NATS_URL.to_string());
+            match NatsAlertTransport::connect(&url).await {
+                Ok(transport) => Arc::new(transport),
+                Err(e) => {
+                    error!("Failed to connect NATS alert transport at {}: {}; falling back to gRPC", url, e);
+                    Arc::new(GrpcAlertTransport)
+                }
+            }
+        }
+        Ok("websocket") => {
+            let url = env::var("ALERT_WS_URL").unwrap_or_else(|_| DEFAULT_ALERT_WS_URL.to_string());
+            match WebSocketClient::new(&url).await {
+                Ok(transport) => Arc::new(transport),
+                Err(e) => {
+                    error!("Failed to connect WebSocket alert transport at {}: {}; falling back to gRPC", url, e);
+                    Arc::new(GrpcAlertTransport)
+                }
+            }
+        }
+        _ => Arc::new(GrpcAlertTransport),
+    }
+}
 
-fn main() {
+#[tokio::main]
+async fn main() {
     // Initialize the logger
     env::set_var("RUST_LOG", "info");
     env_logger::init();
 
     let mut history = MetricHistory::new();
-    let runtime = Runtime::new().unwrap();
-    let client = runtime.block_on(async {});
+    let http_client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(5))
+        .build()
+        .unwrap();
+
+    let alert_transport = Arc::new(RateLimitedTransport::new(
+        select_alert_transport().await,
+        MAX_CRITICAL_ALERTS_PER_MINUTE,
+    ));
+
+    // Fires once on Ctrl+C/SIGTERM; cloned into every task (and watched in
+    // the main loop below) so the whole process actually exits instead of
+    // only closing the dashboard socket.
+    let (shutdown_tx, mut shutdown_rx) = tokio::sync::watch::channel(false);
+    tokio::spawn({
+        let shutdown_tx = shutdown_tx.clone();
+        async move {
+            shutdown_signal().await;
+            let _ = shutdown_tx.send(true);
+        }
+    });
+
+    // Flushes a subject's coalesced suppression summary on its own once it
+    // goes idle, so one that never becomes Critical again doesn't lose its
+    // dropped-cycle count waiting on a send that's never coming.
+    Arc::clone(&alert_transport).spawn_idle_flush(shutdown_rx.clone());
+
+    let exporter = Arc::new(MetricsExporter::new());
+    let exporter_task = {
+        let exporter = Arc::clone(&exporter);
+        let mut shutdown_rx = shutdown_rx.clone();
+        tokio::spawn(async move {
+            let addr = METRICS_EXPORTER_ADDR.parse().unwrap();
+            let graceful_shutdown = async move {
+                let _ = shutdown_rx.changed().await;
+            };
+            if let Err(e) = exporter.serve(addr, graceful_shutdown).await {
+                error!("Metrics exporter failed: {}", e);
+            }
+        })
+    };
+
+    let metrics_server = Arc::new(MetricsServer::new());
+    let metrics_server_task = {
+        let metrics_server = Arc::clone(&metrics_server);
+        let mut shutdown_rx = shutdown_rx.clone();
+        tokio::spawn(async move {
+            let addr = METRICS_SERVER_ADDR.parse().unwrap();
+            let graceful_shutdown = async move {
+                let _ = shutdown_rx.changed().await;
+            };
+            if let Err(e) = metrics_server.serve(addr, graceful_shutdown).await {
+                error!("Metrics stream server failed: {}", e);
+            }
+        })
+    };
+
+    let mut ticker = tokio::time::interval(MONITORING_INTERVAL);
 
     loop {
-        // Collect system metrics
-        let (cpu_usage, ram_usage, disk_usage, network_latency) = collect_metrics();
+        tokio::select! {
+            _ = ticker.tick() => {}
+            _ = shutdown_rx.changed() => {
+                info!("Shutdown signal received, stopping monitoring loop");
+                break;
+            }
+        }
+
+        // Collect system metrics; sysinfo's synchronous collection runs on
+        // a blocking thread concurrently with the async network probe, so
+        // a slow probe never delays CPU/RAM/disk sampling.
+        let (cpu_usage, ram_usage, disk_usage, network_latency) = collect_metrics(&http_client).await;
 
         // Add metrics to history
         history.add(cpu_usage, ram_usage, disk_usage, network_latency);
+        exporter.observe_latency(network_latency);
 
         if history.data.len() >= 20 {
             // Analyze the latest metrics for anomalies
             let metrics_status = analyze_status(&mut history);
 
+            // Update the exporter's view of the latest metrics
+            exporter.update(&metrics_status, &history.smoothed_scores);
+
+            // Push the snapshot to any subscribed dashboard clients
+            metrics_server.publish(&metrics_status);
+
             // Log the metrics and their status
-            log_status(&metrics_status);
+            log_status(&metrics_status, &exporter);
 
-            // Send message to server if critical
+            // Send message to server if critical, debounced by the
+            // per-subject token bucket so consecutive critical cycles don't
+            // flood the downstream consumer.
             if metrics_status.overall_status == MetricStatus::Critical {
-                runtime.block_on(async {
-                    // let message = WSMessage {
-                    //     sub: "CriticalAlert".to_string(),
-                    //     payload: Some(format!(
-                    //         "Critical status detected: CPU: {:.2}, RAM: {:.2}, Disk: {:.2}, Network: {:.2}",
-                    //         metrics_status.cpu.value,
-                    //         metrics_status.ram.value,
-                    //         metrics_status.disk.value,
-                    //         metrics_status.network.value
-                    //     )),
-                    //     reply_sub: None,
-                    //     error: None,
-                    // };
-
-                    // if let Err(e) = client.send_and_wait_for_reply(message).await {
-                    //     error!("Failed to send critical alert: {}", e);
-                    // }
-                });
+                let message = WSMessage {
+                    sub: "CriticalAlert".to_string(),
+                    payload: Some(format!(
+                        "Critical status detected: CPU: {:.2}, RAM: {:.2}, Disk: {:.2}, Network: {:.2} (transport: {:?})",
+                        metrics_status.cpu.value,
+                        metrics_status.ram.value,
+                        metrics_status.disk.value,
+                        metrics_status.network.value,
+                        alert_transport.connection_state()
+                    )),
+                    reply_sub: None,
+                    error: None,
+                };
+
+                if let Err(e) = alert_transport
+                    .send_or_coalesce(message, metrics_status.overall_score)
+                    .await
+                {
+                    error!("Failed to send critical alert: {}", e);
+                }
             }
         } else {
             println!("Collecting data... ({}/{})", history.data.len(), 20);
         }
+    }
 
-        // Sleep until the next monitoring interval
-        thread::sleep(MONITORING_INTERVAL);
+    // Wait for both servers' graceful shutdown to finish in-flight HTTP
+    // requests before the process exits. Open dashboard WebSocket streams
+    // are not part of this wait.
+    let (exporter_result, metrics_server_result) = tokio::join!(exporter_task, metrics_server_task);
+    if let Err(e) = exporter_result {
+        error!("Metrics exporter task panicked: {}", e);
+    }
+    if let Err(e) = metrics_server_result {
+        error!("Metrics stream server task panicked: {}", e);
     }
 }
 
-/// Collects the current system metrics
-fn collect_metrics() -> (f64, f64, f64, f64) {
+/// Collects the current system metrics, moving sysinfo's synchronous
+/// collection onto a blocking thread so it runs concurrently with the async
+/// network probe instead of gating behind it.
+async fn collect_metrics(http_client: &reqwest::Client) -> (f64, f64, f64, f64) {
+    let sysinfo_task = tokio::task::spawn_blocking(collect_sysinfo_metrics);
+    let network_latency = check_network_latency(http_client, "https://www.google.com").await;
+    let (cpu_usage, ram_usage, disk_usage) = sysinfo_task.await.expect("sysinfo collection task panicked");
+
+    (cpu_usage, ram_usage, disk_usage, network_latency)
+}
+
+/// Synchronously collects CPU/RAM/disk usage via `sysinfo`; run via
+/// `spawn_blocking` since `sysinfo` refreshes block the calling thread.
+fn collect_sysinfo_metrics() -> (f64, f64, f64) {
     let mut sys = System::new_all();
     sys.refresh_all();
 
@@ -94,21 +235,13 @@ fn collect_metrics() -> (f64, f64, f64, f64) {
         .sum::<f64>()
         / disks.len() as f64; // Average disk usage across disks
 
-    // Network latency to a well-known website
-    let network_latency = check_network_latency("https://www.google.com");
-
-    (cpu_usage, ram_usage, disk_usage, network_latency)
+    (cpu_usage, ram_usage, disk_usage)
 }
 
-/// Checks network latency by sending a request to the given URL
-fn check_network_latency(url: &str) -> f64 {
+/// Checks network latency by sending an async request to the given URL
+async fn check_network_latency(client: &reqwest::Client, url: &str) -> f64 {
     let start = Instant::now();
-    let client = reqwest::blocking::Client::builder()
-        .timeout(Duration::from_secs(5))
-        .build()
-        .unwrap();
-
-    match client.get(url).send() {
+    match client.get(url).send().await {
         Ok(_) => start.elapsed().as_secs_f64() * 1000.0, // Convert to milliseconds
         Err(_) => f64::MAX,                              // Use a large value to indicate failure
     }
@@ -151,6 +284,7 @@ fn analyze_status(history: &mut MetricHistory) -> Metrics {
                     status: MetricStatus::Normal,
                 },
                 overall_status: MetricStatus::Normal,
+                overall_score: 0.0,
             };
         }
     };
@@ -239,6 +373,7 @@ fn analyze_status(history: &mut MetricHistory) -> Metrics {
             status: statuses[3].clone(),
         },
         overall_status,
+        overall_score,
     }
 }
 
@@ -256,8 +391,9 @@ fn determine_status(score: f64, threshold: f64) -> MetricStatus {
 }
 
 /// Log the status
-fn log_status(metrics: &Metrics) {
+fn log_status(metrics: &Metrics, exporter: &MetricsExporter) {
     let now = Utc::now();
     let json_output = serde_json::to_string(metrics).unwrap();
     info!("[{}] Metrics: {}", now, json_output);
+    exporter.record_status(&metrics.overall_status);
 }
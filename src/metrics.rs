@@ -61,16 +61,17 @@ pub enum MetricStatus {
     Critical,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Clone)]
 pub struct Metrics {
     pub cpu: Metric,
     pub ram: Metric,
     pub disk: Metric,
     pub network: Metric,
     pub overall_status: MetricStatus,
+    pub overall_score: f64,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Clone)]
 pub struct Metric {
     pub value: f64,
     pub status: MetricStatus,
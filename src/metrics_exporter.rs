@@ -0,0 +1,196 @@
+use crate::metrics::{MetricStatus, Metrics};
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Request, Response, Server};
+use log::{error, info, warn};
+use prometheus::{Encoder, Gauge, Histogram, HistogramOpts, IntCounterVec, Opts, Registry, TextEncoder};
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+
+/// Address the Prometheus `/metrics` endpoint is served on.
+pub const METRICS_EXPORTER_ADDR: &str = "0.0.0.0:9898";
+
+/// Cheap, `Copy` snapshot of the latest analyzed metrics, refreshed by the
+/// main loop and read by the scrape handler so every `GET /metrics` reports
+/// fresh values without needing to touch the `MetricHistory` itself.
+#[derive(Debug, Clone, Copy, Default)]
+struct Snapshot {
+    cpu: f64,
+    ram: f64,
+    disk: f64,
+    network: f64,
+    smoothed_scores: [f64; 4],
+}
+
+/// Exposes the monitor's live metrics and anomaly scores as a Prometheus
+/// text-format endpoint so the process can be scraped by standard
+/// observability stacks instead of only writing log lines.
+pub struct MetricsExporter {
+    registry: Registry,
+    cpu_gauge: Gauge,
+    ram_gauge: Gauge,
+    disk_gauge: Gauge,
+    network_gauge: Gauge,
+    cpu_score_gauge: Gauge,
+    ram_score_gauge: Gauge,
+    disk_score_gauge: Gauge,
+    network_score_gauge: Gauge,
+    network_latency_histogram: Histogram,
+    status_counter: IntCounterVec,
+    latest: Arc<Mutex<Snapshot>>,
+}
+
+impl MetricsExporter {
+    pub fn new() -> Self {
+        let registry = Registry::new();
+
+        let cpu_gauge = Gauge::new("system_cpu_usage_percent", "Current CPU usage percentage").unwrap();
+        let ram_gauge = Gauge::new("system_ram_usage_percent", "Current RAM usage percentage").unwrap();
+        let disk_gauge = Gauge::new("system_disk_usage_percent", "Current disk usage percentage").unwrap();
+        let network_gauge = Gauge::new("system_network_latency_ms", "Current network latency in milliseconds").unwrap();
+
+        let cpu_score_gauge = Gauge::new("system_cpu_anomaly_score", "Smoothed EWMA anomaly score for CPU").unwrap();
+        let ram_score_gauge = Gauge::new("system_ram_anomaly_score", "Smoothed EWMA anomaly score for RAM").unwrap();
+        let disk_score_gauge = Gauge::new("system_disk_anomaly_score", "Smoothed EWMA anomaly score for disk").unwrap();
+        let network_score_gauge = Gauge::new("system_network_anomaly_score", "Smoothed EWMA anomaly score for network").unwrap();
+
+        let network_latency_histogram = Histogram::with_opts(
+            HistogramOpts::new("system_network_latency_seconds", "Observed network latency samples in seconds")
+                .buckets(vec![0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0]),
+        )
+        .unwrap();
+
+        let status_counter = IntCounterVec::new(
+            Opts::new("system_status_total", "Count of monitoring cycles by overall status"),
+            &["status"],
+        )
+        .unwrap();
+
+        registry.register(Box::new(cpu_gauge.clone())).unwrap();
+        registry.register(Box::new(ram_gauge.clone())).unwrap();
+        registry.register(Box::new(disk_gauge.clone())).unwrap();
+        registry.register(Box::new(network_gauge.clone())).unwrap();
+        registry.register(Box::new(cpu_score_gauge.clone())).unwrap();
+        registry.register(Box::new(ram_score_gauge.clone())).unwrap();
+        registry.register(Box::new(disk_score_gauge.clone())).unwrap();
+        registry.register(Box::new(network_score_gauge.clone())).unwrap();
+        registry.register(Box::new(network_latency_histogram.clone())).unwrap();
+        registry.register(Box::new(status_counter.clone())).unwrap();
+
+        Self {
+            registry,
+            cpu_gauge,
+            ram_gauge,
+            disk_gauge,
+            network_gauge,
+            cpu_score_gauge,
+            ram_score_gauge,
+            disk_score_gauge,
+            network_score_gauge,
+            network_latency_histogram,
+            status_counter,
+            latest: Arc::new(Mutex::new(Snapshot::default())),
+        }
+    }
+
+    /// Called by the main loop after `analyze_status` so the next scrape
+    /// reflects the latest analyzed metrics and EWMA scores.
+    pub fn update(&self, metrics: &Metrics, smoothed_scores: &[f64; 4]) {
+        let mut latest = self.latest.lock().unwrap();
+        latest.cpu = metrics.cpu.value;
+        latest.ram = metrics.ram.value;
+        latest.disk = metrics.disk.value;
+        latest.network = metrics.network.value;
+        latest.smoothed_scores = *smoothed_scores;
+    }
+
+    /// Called once per collection cycle so the histogram captures every
+    /// sample rather than only the value visible at scrape time. Skips
+    /// `check_network_latency`'s `f64::MAX` failure sentinel instead of
+    /// recording it, since it would otherwise land in the `+Inf` bucket and
+    /// permanently blow up the histogram's `_sum`.
+    pub fn observe_latency(&self, network_latency_ms: f64) {
+        if network_latency_ms == f64::MAX {
+            warn!("Skipping latency observation: network probe failed");
+            return;
+        }
+        self.network_latency_histogram.observe(network_latency_ms / 1000.0);
+    }
+
+    /// Called from `log_status` each cycle to track how often the monitor
+    /// spends time in each overall status.
+    pub fn record_status(&self, status: &MetricStatus) {
+        let label = match status {
+            MetricStatus::Normal => "normal",
+            MetricStatus::Warning => "warning",
+            MetricStatus::Critical => "critical",
+        };
+        self.status_counter.with_label_values(&[label]).inc();
+    }
+
+    fn refresh_gauges(&self) {
+        let latest = *self.latest.lock().unwrap();
+        self.cpu_gauge.set(latest.cpu);
+        self.ram_gauge.set(latest.ram);
+        self.disk_gauge.set(latest.disk);
+        self.network_gauge.set(latest.network);
+        self.cpu_score_gauge.set(latest.smoothed_scores[0]);
+        self.ram_score_gauge.set(latest.smoothed_scores[1]);
+        self.disk_score_gauge.set(latest.smoothed_scores[2]);
+        self.network_score_gauge.set(latest.smoothed_scores[3]);
+    }
+
+    async fn handle_scrape(&self) -> Response<Body> {
+        self.refresh_gauges();
+
+        let metric_families = self.registry.gather();
+        let encoder = TextEncoder::new();
+        let mut buffer = Vec::new();
+        if let Err(e) = encoder.encode(&metric_families, &mut buffer) {
+            error!("Failed to encode Prometheus metrics: {}", e);
+            return Response::builder()
+                .status(hyper::StatusCode::INTERNAL_SERVER_ERROR)
+                .body(Body::from("failed to encode metrics"))
+                .unwrap();
+        }
+
+        Response::builder()
+            .header(hyper::header::CONTENT_TYPE, encoder.format_type())
+            .body(Body::from(buffer))
+            .unwrap()
+    }
+
+    /// Runs the `/metrics` HTTP server until `shutdown` resolves, letting
+    /// any in-flight scrape complete before the socket closes.
+    pub async fn serve(
+        self: Arc<Self>,
+        addr: SocketAddr,
+        shutdown: impl std::future::Future<Output = ()> + Send + 'static,
+    ) -> Result<(), hyper::Error> {
+        let make_svc = make_service_fn(move |_conn| {
+            let exporter = Arc::clone(&self);
+            async move {
+                Ok::<_, Infallible>(service_fn(move |req: Request<Body>| {
+                    let exporter = Arc::clone(&exporter);
+                    async move {
+                        let response = if req.uri().path() == "/metrics" {
+                            exporter.handle_scrape().await
+                        } else {
+                            Response::builder()
+                                .status(hyper::StatusCode::NOT_FOUND)
+                                .body(Body::empty())
+                                .unwrap()
+                        };
+                        Ok::<_, Infallible>(response)
+                    }
+                }))
+            }
+        });
+
+        info!("Serving Prometheus metrics on http://{}/metrics", addr);
+        Server::bind(&addr)
+            .serve(make_svc)
+            .with_graceful_shutdown(shutdown)
+            .await
+    }
+}
@@ -0,0 +1,173 @@
+use crate::metrics::Metrics;
+use crate::socket::WSMessage;
+use futures_util::{SinkExt, StreamExt};
+use hyper::service::{make_service_fn, service_fn};
+use hyper::upgrade::Upgraded;
+use hyper::{Body, Request, Response, Server, StatusCode};
+use hyper_tungstenite::{is_upgrade_request, tungstenite::Message, WebSocketStream};
+use log::{error, info, warn};
+use std::convert::Infallible;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::sync::broadcast;
+
+/// Subject a dashboard client subscribes to in order to receive every
+/// analyzed `Metrics` snapshot as the main loop produces it.
+pub const METRICS_STREAM_SUBJECT: &str = "metrics.stream";
+
+/// Address the metrics streaming WebSocket server listens on.
+pub const METRICS_SERVER_ADDR: &str = "0.0.0.0:9899";
+
+const BROADCAST_CAPACITY: usize = 128;
+
+/// Fans out every analyzed `Metrics` snapshot to subscribed dashboard
+/// clients over a broadcast channel, so consumers don't each need to
+/// re-run collection to see live data.
+pub struct MetricsServer {
+    tx: broadcast::Sender<Metrics>,
+}
+
+impl MetricsServer {
+    pub fn new() -> Self {
+        let (tx, _rx) = broadcast::channel(BROADCAST_CAPACITY);
+        Self { tx }
+    }
+
+    /// Publishes a freshly analyzed snapshot to all current subscribers.
+    /// No-op if nobody is currently subscribed.
+    pub fn publish(&self, metrics: &Metrics) {
+        let _ = self.tx.send(metrics.clone());
+    }
+
+    /// Runs the WebSocket upgrade server until `shutdown` resolves, then
+    /// stops accepting connections and lets in-flight HTTP requests finish.
+    /// Upgraded dashboard sockets run in their own tasks and are not closed
+    /// by the shutdown; they end when the process exits, without a close
+    /// frame.
+    pub async fn serve(
+        self: Arc<Self>,
+        addr: SocketAddr,
+        shutdown: impl Future<Output = ()> + Send + 'static,
+    ) -> Result<(), hyper::Error> {
+        let make_svc = make_service_fn(move |_conn| {
+            let server = Arc::clone(&self);
+            async move {
+                Ok::<_, Infallible>(service_fn(move |req: Request<Body>| {
+                    let server = Arc::clone(&server);
+                    async move { Ok::<_, Infallible>(server.handle_request(req).await) }
+                }))
+            }
+        });
+
+        info!("Serving metrics stream WebSocket on ws://{}", addr);
+        Server::bind(&addr)
+            .serve(make_svc)
+            .with_graceful_shutdown(shutdown)
+            .await
+    }
+
+    async fn handle_request(self: Arc<Self>, mut req: Request<Body>) -> Response<Body> {
+        if !is_upgrade_request(&req) {
+            return Response::builder()
+                .status(StatusCode::BAD_REQUEST)
+                .body(Body::from("expected a WebSocket upgrade"))
+                .unwrap();
+        }
+
+        match hyper_tungstenite::upgrade(&mut req, None) {
+            Ok((response, websocket)) => {
+                tokio::spawn(async move {
+                    match websocket.await {
+                        Ok(ws_stream) => self.handle_socket(ws_stream).await,
+                        Err(e) => error!("WebSocket upgrade failed: {}", e),
+                    }
+                });
+                response
+            }
+            Err(e) => {
+                error!("Failed to build WebSocket upgrade response: {}", e);
+                Response::builder()
+                    .status(StatusCode::INTERNAL_SERVER_ERROR)
+                    .body(Body::empty())
+                    .unwrap()
+            }
+        }
+    }
+
+    async fn handle_socket(self: Arc<Self>, mut ws_stream: WebSocketStream<Upgraded>) {
+        let subscribed = matches!(
+            ws_stream.next().await,
+            Some(Ok(Message::Text(text)))
+                if serde_json::from_str::<WSMessage>(&text)
+                    .map(|msg| msg.sub == METRICS_STREAM_SUBJECT)
+                    .unwrap_or(false)
+        );
+
+        if !subscribed {
+            warn!("Dashboard client did not subscribe to {}; closing", METRICS_STREAM_SUBJECT);
+            let _ = ws_stream.close(None).await;
+            return;
+        }
+
+        let mut rx = self.tx.subscribe();
+        loop {
+            tokio::select! {
+                snapshot = rx.recv() => {
+                    match snapshot {
+                        Ok(metrics) => {
+                            let msg = WSMessage {
+                                sub: METRICS_STREAM_SUBJECT.to_string(),
+                                payload: Some(serde_json::to_string(&metrics).unwrap()),
+                                reply_sub: None,
+                                error: None,
+                            };
+                            let json = serde_json::to_string(&msg).unwrap();
+                            if ws_stream.send(Message::Text(json)).await.is_err() {
+                                break;
+                            }
+                        }
+                        Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                            warn!("Dashboard subscriber lagged, skipped {} snapshots", skipped);
+                        }
+                        Err(broadcast::error::RecvError::Closed) => break,
+                    }
+                }
+                inbound = ws_stream.next() => {
+                    match inbound {
+                        Some(Ok(Message::Close(_))) | None => break,
+                        Some(Err(e)) => {
+                            error!("Dashboard WebSocket error: {}", e);
+                            break;
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Resolves on Ctrl+C or SIGTERM, for driving `Server::with_graceful_shutdown`.
+pub async fn shutdown_signal() {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c().await.expect("failed to install Ctrl+C handler");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("failed to install SIGTERM handler")
+            .recv()
+            .await;
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
+    info!("Shutdown signal received, stopping metrics stream server");
+}
@@ -1,11 +1,21 @@
 use futures_util::{SinkExt, StreamExt};
-use log::{error, info};
+use log::{error, info, warn};
+use rand::Rng;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicU8, Ordering};
 use std::sync::Arc;
+use tokio::net::TcpStream;
 use tokio::sync::{mpsc, Mutex};
-use tokio::time::{timeout, Duration};
-use tokio_tungstenite::{connect_async, tungstenite::protocol::Message, MaybeTlsStream};
+use tokio::time::{interval, timeout, Duration, Instant};
+use tokio_tungstenite::{connect_async, tungstenite::protocol::Message, MaybeTlsStream, WebSocketStream};
+
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+const PING_INTERVAL: Duration = Duration::from_secs(15);
+const PONG_TIMEOUT: Duration = Duration::from_secs(30);
+
+type WsStream = WebSocketStream<MaybeTlsStream<TcpStream>>;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct WSMessage {
@@ -15,61 +25,209 @@ pub struct WSMessage {
     pub error: Option<String>,
 }
 
+/// Observable health of the underlying WebSocket connection, so callers
+/// (e.g. `main`) can annotate alerts with transport health instead of just
+/// assuming the socket is up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectionState {
+    Connected,
+    Reconnecting,
+    Disconnected,
+}
+
+impl From<u8> for ConnectionState {
+    fn from(value: u8) -> Self {
+        match value {
+            0 => ConnectionState::Connected,
+            1 => ConnectionState::Reconnecting,
+            _ => ConnectionState::Disconnected,
+        }
+    }
+}
+
+/// Why `run_connection` returned, so `supervisor` can tell a recoverable
+/// network hiccup apart from the owning `WebSocketClient` being dropped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ConnectionOutcome {
+    NeedsReconnect,
+    ShuttingDown,
+}
+
 pub struct WebSocketClient {
-    ws_stream: Arc<Mutex<tokio_tungstenite::WebSocketStream<MaybeTlsStream<tokio::net::TcpStream>>>>,
     sender: mpsc::Sender<WSMessage>,
     pending_replies: Arc<Mutex<HashMap<String, mpsc::Sender<WSMessage>>>>,
+    state: Arc<AtomicU8>,
 }
 
 impl WebSocketClient {
     pub async fn new(url: &str) -> Result<Self, String> {
+        let ws_stream = Self::connect(url).await?;
+
+        let (tx, rx) = mpsc::channel::<WSMessage>(32);
+        let pending_replies: Arc<Mutex<HashMap<String, mpsc::Sender<WSMessage>>>> = Arc::new(Mutex::new(HashMap::new()));
+        let state = Arc::new(AtomicU8::new(ConnectionState::Connected as u8));
+
+        tokio::spawn(Self::supervisor(
+            url.to_string(),
+            ws_stream,
+            rx,
+            Arc::clone(&pending_replies),
+            Arc::clone(&state),
+        ));
+
+        Ok(Self {
+            sender: tx,
+            pending_replies,
+            state,
+        })
+    }
+
+    async fn connect(url: &str) -> Result<WsStream, String> {
         let (ws_stream, _) = connect_async(url).await.map_err(|e| format!("Failed to connect: {}", e))?;
         info!("Connected to WebSocket server at {}", url);
+        Ok(ws_stream)
+    }
 
-        let (tx, mut rx) = mpsc::channel::<WSMessage>(32);
-        let pending_replies: Arc<Mutex<HashMap<String, mpsc::Sender<WSMessage>>>> = Arc::new(Mutex::new(HashMap::new()));
-        let ws_stream = Arc::new(Mutex::new(ws_stream));
-
-        let ws_stream_clone = Arc::clone(&ws_stream);
-        tokio::spawn(async move {
-            while let Some(msg) = rx.recv().await {
-                let json_msg = serde_json::to_string(&msg).unwrap();
-                if let Err(e) = ws_stream_clone.lock().await.send(Message::Text(json_msg)).await {
-                    error!("Error sending message: {}", e);
+    /// Current connection health, suitable for annotating outgoing alerts.
+    pub fn connection_state(&self) -> ConnectionState {
+        ConnectionState::from(self.state.load(Ordering::Relaxed))
+    }
+
+    /// Owns the connection lifecycle across reconnects: pumps messages while
+    /// connected, and on read error or server close, reconnects with
+    /// exponential backoff (1s doubling to a 60s cap, plus jitter) instead of
+    /// leaving the client permanently dead.
+    async fn supervisor(
+        url: String,
+        mut ws_stream: WsStream,
+        mut rx: mpsc::Receiver<WSMessage>,
+        pending_replies: Arc<Mutex<HashMap<String, mpsc::Sender<WSMessage>>>>,
+        state: Arc<AtomicU8>,
+    ) {
+        loop {
+            state.store(ConnectionState::Connected as u8, Ordering::Relaxed);
+
+            if Self::run_connection(&mut ws_stream, &mut rx, &pending_replies).await == ConnectionOutcome::ShuttingDown {
+                // The owning `WebSocketClient` (and its `mpsc::Sender`) was
+                // dropped; there's no one left to reconnect for.
+                Self::fail_pending_replies(&pending_replies, "WebSocketClient dropped, shutting down").await;
+                state.store(ConnectionState::Disconnected as u8, Ordering::Relaxed);
+                return;
+            }
+
+            // Fail outstanding replies immediately rather than leaving
+            // callers to hang until their 60s request timeout.
+            Self::fail_pending_replies(&pending_replies, "Connection lost, reconnecting").await;
+            state.store(ConnectionState::Reconnecting as u8, Ordering::Relaxed);
+
+            let mut backoff = INITIAL_BACKOFF;
+            loop {
+                let jitter = Duration::from_millis(rand::thread_rng().gen_range(0..250));
+                tokio::time::sleep(backoff + jitter).await;
+
+                match Self::connect(&url).await {
+                    Ok(stream) => {
+                        ws_stream = stream;
+                        break;
+                    }
+                    Err(e) => {
+                        warn!("Reconnect to {} failed: {}", url, e);
+                        backoff = (backoff * 2).min(MAX_BACKOFF);
+                    }
                 }
             }
-        });
-
-        let pending_replies_clone = Arc::clone(&pending_replies);
-        let ws_stream_clone = Arc::clone(&ws_stream);
-        tokio::spawn(async move {
-            while let Some(msg) = ws_stream_clone.lock().await.next().await {
-                if let Ok(Message::Text(text)) = msg {
-                    match serde_json::from_str::<WSMessage>(&text) {
-                        Ok(ws_msg) => {
-                            info!("Received: {:?}", ws_msg);
-                            let mut pending = pending_replies_clone.lock().await;
-                            if let Some(sender) = pending.remove(&ws_msg.sub) {
-                                let _ = sender.send(ws_msg).await;
+        }
+    }
+
+    /// Pumps outbound/inbound messages and periodic ping keepalives for a
+    /// single connection, returning once the connection needs to be
+    /// re-established (error, close, or a missed pong) or the owning
+    /// `WebSocketClient` has been dropped and there's nothing left to pump.
+    async fn run_connection(
+        ws_stream: &mut WsStream,
+        rx: &mut mpsc::Receiver<WSMessage>,
+        pending_replies: &Arc<Mutex<HashMap<String, mpsc::Sender<WSMessage>>>>,
+    ) -> ConnectionOutcome {
+        let mut ping_interval = interval(PING_INTERVAL);
+        let mut last_pong = Instant::now();
+
+        loop {
+            tokio::select! {
+                outbound = rx.recv() => {
+                    match outbound {
+                        Some(msg) => {
+                            let json_msg = serde_json::to_string(&msg).unwrap();
+                            if let Err(e) = ws_stream.send(Message::Text(json_msg)).await {
+                                error!("Error sending message: {}", e);
+                                return ConnectionOutcome::NeedsReconnect;
                             }
                         }
-                        Err(e) => error!("Failed to deserialize message: {}", e),
+                        // Sender half dropped: the WebSocketClient is gone, so
+                        // this is an intentional shutdown, not a reconnect.
+                        None => return ConnectionOutcome::ShuttingDown,
+                    }
+                }
+                inbound = ws_stream.next() => {
+                    match inbound {
+                        Some(Ok(Message::Text(text))) => {
+                            match serde_json::from_str::<WSMessage>(&text) {
+                                Ok(ws_msg) => {
+                                    info!("Received: {:?}", ws_msg);
+                                    let mut pending = pending_replies.lock().await;
+                                    if let Some(sender) = pending.remove(&ws_msg.sub) {
+                                        let _ = sender.send(ws_msg).await;
+                                    }
+                                }
+                                Err(e) => error!("Failed to deserialize message: {}", e),
+                            }
+                        }
+                        Some(Ok(Message::Pong(_))) => {
+                            last_pong = Instant::now();
+                        }
+                        Some(Ok(Message::Close(_))) => {
+                            info!("Connection closed by server");
+                            return ConnectionOutcome::NeedsReconnect;
+                        }
+                        Some(Err(e)) => {
+                            error!("WebSocket error: {}", e);
+                            return ConnectionOutcome::NeedsReconnect;
+                        }
+                        None => {
+                            info!("Connection stream ended");
+                            return ConnectionOutcome::NeedsReconnect;
+                        }
+                        _ => {}
+                    }
+                }
+                _ = ping_interval.tick() => {
+                    if last_pong.elapsed() > PONG_TIMEOUT {
+                        warn!("No pong received within {:?}; treating connection as half-open", PONG_TIMEOUT);
+                        return ConnectionOutcome::NeedsReconnect;
+                    }
+                    if let Err(e) = ws_stream.send(Message::Ping(Vec::new())).await {
+                        error!("Failed to send keepalive ping: {}", e);
+                        return ConnectionOutcome::NeedsReconnect;
                     }
-                } else if let Ok(Message::Close(_)) = msg {
-                    info!("Connection closed by server");
-                    break;
-                } else if let Err(e) = msg {
-                    error!("WebSocket error: {}", e);
-                    break;
                 }
             }
-        });
+        }
+    }
 
-        Ok(Self {
-            ws_stream,
-            sender: tx,
-            pending_replies,
-        })
+    async fn fail_pending_replies(
+        pending_replies: &Arc<Mutex<HashMap<String, mpsc::Sender<WSMessage>>>>,
+        reason: &str,
+    ) {
+        let mut pending = pending_replies.lock().await;
+        for (sub, sender) in pending.drain() {
+            let _ = sender
+                .send(WSMessage {
+                    sub,
+                    payload: None,
+                    reply_sub: None,
+                    error: Some(reason.to_string()),
+                })
+                .await;
+        }
     }
 
     pub async fn send_and_wait_for_reply(&self, msg: WSMessage) -> Result<WSMessage, String> {